@@ -1,14 +1,19 @@
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::collections::hash_map::DefaultHasher;
 use std::fmt;
 use std::hash::{Hash, Hasher};
 
+use crate::function::{FormatOptions, FormatterRegistry};
+use crate::langid::LanguageIdentifier;
+use crate::plural::{plural_category, PluralOperands};
+
 
 pub struct PHTypeAttributes {
-    enumerated: bool,
+    pub(crate) enumerated: bool,
 }
 
-#[derive(Hash, Eq, PartialEq, Debug)]
+#[derive(Hash, Eq, PartialEq, Debug, Clone)]
 pub enum PlaceholderType {
     UNKNOWN, // from Google protobuf style guide, but is this necessary? I think not
     GENDER,
@@ -36,6 +41,7 @@ pub fn ph_type_attrs_map() -> HashMap<PlaceholderType, PHTypeAttributes> {
     m
 }
 
+#[derive(Debug)]
 pub struct Placeholder {
     // id & name for PH, used for val interpolation in the formatted string.
     // Let the user decide whether this should be unique or shared
@@ -48,11 +54,11 @@ pub struct Placeholder {
     // SPAN2, ... to indicate that the contents may very well differ.
     // and <b> and <i> tags may just all be B and I because they are
     // semantically same, and therefore interchangeable.
-    id: String,
+    pub(crate) id: String,
 
     // type of the PH.
     // See notes for PlaceholderType for nuances of PH types.
-    ph_type: PlaceholderType,
+    pub(crate) ph_type: PlaceholderType,
 
     // a user-supplied text representation of the PH, if available.
     // For PHs that are created by the user (or user's l10n tool),
@@ -61,7 +67,7 @@ pub struct Placeholder {
     // we already know the text that the PH is "holding the place" for.
     // If not present, then the value must be present in the map 
     // `SingleMessage.ph_vals` that is keyed by this PH's `Placeholder.id`.
-    default_text_val: Option<String>,
+    pub(crate) default_text_val: Option<String>,
 }
 
 impl fmt::Display for Placeholder {
@@ -75,7 +81,7 @@ impl fmt::Display for Placeholder {
 // during the formatting phase.
 #[derive(Clone, Eq, Debug)] // impl for Hash and PartialEq below
 pub struct PHValsMap {
-    map: HashMap<String, String>,
+    pub(crate) map: HashMap<String, String>,
 }
 
 impl std::hash::Hash for PHValsMap {
@@ -111,8 +117,174 @@ impl fmt::Display for PHValsMap {
     }
 }
 
+// Locale-sensitive options for rendering a numeric `ArgValue`, in the spirit
+// of `Intl.NumberFormat`. Defaults mirror a plain integer-or-decimal render.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NumberFormatOptions {
+    pub minimum_fraction_digits: usize,
+    pub maximum_fraction_digits: usize,
+    pub use_grouping: bool,
+}
+
+impl Default for NumberFormatOptions {
+    fn default() -> Self {
+        NumberFormatOptions {
+            minimum_fraction_digits: 0,
+            maximum_fraction_digits: 3,
+            use_grouping: true,
+        }
+    }
+}
+
+// A typed placeholder argument, borrowed in spirit from Fluent's `FluentValue`.
+// Replaces the former stringly-typed `ph_vals` so a `{$count}` can render as
+// `1,234` vs `1234` per locale and still feed the plural operands.
+#[derive(Clone, Debug)]
+pub enum ArgValue {
+    Number(f64, NumberFormatOptions),
+    String(String),
+    None,
+}
+
+impl ArgValue {
+    // Convenience constructor for a plain number with default options.
+    pub fn number(value: f64) -> ArgValue {
+        ArgValue::Number(value, NumberFormatOptions::default())
+    }
+
+    // Render the value for interpolation into the given locale.
+    pub fn format(&self, locale: &str) -> String {
+        match self {
+            ArgValue::Number(value, opts) => format_number(*value, opts, locale),
+            ArgValue::String(s) => s.clone(),
+            ArgValue::None => String::new(),
+        }
+    }
+
+    // The locale-independent display form used both as a selection key and as
+    // the basis for hashing/equality (numbers hash by value, not by options).
+    pub fn canonical(&self) -> String {
+        match self {
+            ArgValue::Number(value, _) => format_number(*value, &NumberFormatOptions {
+                minimum_fraction_digits: 0,
+                maximum_fraction_digits: 15,
+                use_grouping: false,
+            }, "und"),
+            ArgValue::String(s) => s.clone(),
+            ArgValue::None => String::new(),
+        }
+    }
+}
+
+impl PartialEq for ArgValue {
+    fn eq(&self, other: &ArgValue) -> bool {
+        self.canonical() == other.canonical()
+    }
+}
+
+impl Eq for ArgValue {}
+
+impl std::hash::Hash for ArgValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.canonical().hash(state);
+    }
+}
+
+impl fmt::Display for ArgValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.canonical())
+    }
+}
+
+// Format a number honoring fraction-digit bounds and locale group/decimal
+// separators. Only a few locales carry bespoke separators; the rest use the
+// `en`-style `,`/`.` pair.
+fn format_number(value: f64, opts: &NumberFormatOptions, locale: &str) -> String {
+    let (group_sep, decimal_sep) = match locale.split(['-', '_']).next().unwrap_or("") {
+        "fr" => ('\u{202f}', ','), // narrow no-break space, comma decimal
+        "de" => ('.', ','),
+        _ => (',', '.'),
+    };
+
+    let negative = value.is_sign_negative() && value != 0.0;
+    let magnitude = value.abs();
+
+    // round to the maximum fraction digits, then build the fraction string.
+    let rounded = format!("{:.*}", opts.maximum_fraction_digits, magnitude);
+    let (int_part, frac_part) = match rounded.split_once('.') {
+        Some((a, b)) => (a.to_string(), b.to_string()),
+        None => (rounded, String::new()),
+    };
+
+    // trim trailing zeros down to the minimum required.
+    let mut frac = frac_part.trim_end_matches('0').to_string();
+    while frac.len() < opts.minimum_fraction_digits {
+        frac.push('0');
+    }
+
+    // group the integer digits.
+    let grouped = if opts.use_grouping {
+        let digits: Vec<char> = int_part.chars().collect();
+        let mut out = String::new();
+        let len = digits.len();
+        for (idx, ch) in digits.iter().enumerate() {
+            if idx > 0 && (len - idx).is_multiple_of(3) {
+                out.push(group_sep);
+            }
+            out.push(*ch);
+        }
+        out
+    } else {
+        int_part
+    };
+
+    let mut result = String::new();
+    if negative {
+        result.push('-');
+    }
+    result.push_str(&grouped);
+    if !frac.is_empty() {
+        result.push(decimal_sep);
+        result.push_str(&frac);
+    }
+    result
+}
+
+// Something that went wrong while interpolating argument values into a
+// pattern. Kept deliberately small; new variants are added as the formatter
+// grows (e.g. message references).
+#[derive(Debug, Eq, PartialEq)]
+pub enum FormatError {
+    // a placeholder had neither a supplied argument value nor a
+    // `default_text_val` to fall back on.
+    MissingValue(String),
+    // a `MESSAGE_REF` pointed at an id that is not in the registry.
+    UnknownReference(String),
+    // resolving a `MESSAGE_REF` re-entered a message already being formatted.
+    CyclicReference(String),
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FormatError::MissingValue(id) => {
+                write!(f, "no value supplied for placeholder `{}`", id)
+            }
+            FormatError::UnknownReference(id) => {
+                write!(f, "unknown message reference `{}`", id)
+            }
+            FormatError::CyclicReference(id) => {
+                write!(f, "cyclic message reference involving `{}`", id)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+#[derive(Debug)]
 pub struct TextPart {
-    text: String,
+    pub(crate) text: String,
 }
 
 impl fmt::Display for TextPart {
@@ -121,9 +293,14 @@ impl fmt::Display for TextPart {
     }
 }
 
+#[derive(Debug)]
 pub enum PatternPart {
     TEXTPART(TextPart),
     PLACEHOLDER(Placeholder),
+    // a reference to another message by id, resolved against the registry at
+    // format time so shared sub-strings (brand names, reusable phrases) need
+    // not be duplicated across `TextUnit` entries.
+    MESSAGE_REF(String),
 }
 
 impl fmt::Display for PatternPart {
@@ -135,13 +312,17 @@ impl fmt::Display for PatternPart {
             PatternPart::PLACEHOLDER(placeholder) => {
                 write!(f, "{}", format!("{}", placeholder))
             }
+            PatternPart::MESSAGE_REF(id) => {
+                write!(f, "{}", format!("{{{}}}", id))
+            }
         };
         result
     }
 }
 
+#[derive(Debug)]
 pub struct MessagePattern {
-    parts: Vec<PatternPart>
+    pub(crate) parts: Vec<PatternPart>
 }
 
 impl fmt::Display for MessagePattern {
@@ -152,13 +333,129 @@ impl fmt::Display for MessagePattern {
     }
 }
 
+#[derive(Debug)]
 pub struct SingleMessage {
     // unique id for the SingleMessage, globally unique.
-    id: String,
+    pub(crate) id: String,
+
+    // the message's locale as a structured language identifier, so plural-rule
+    // lookup and regional fallback can use its language/region components.
+    pub(crate) locale: LanguageIdentifier,
+    pub(crate) pattern: MessagePattern,
+    // runtime argument values, keyed by `Placeholder.id`; now typed via
+    // `ArgValue` rather than the former `HashMap<String, String>`.
+    pub(crate) ph_vals: HashMap<String, ArgValue>,
+}
+
+// A lookup of all known messages by id, used to resolve `MESSAGE_REF` parts.
+pub type MessageRegistry = HashMap<String, MessageType>;
+
+// Pick the `SingleMessage` whose locale best matches `requested`, walking the
+// BCP-47 fallback chain (e.g. `en-GB` -> `en`). Returns `None` if no candidate
+// shares the requested language.
+pub fn resolve_message<'a>(
+    requested: &LanguageIdentifier,
+    candidates: &'a [SingleMessage],
+) -> Option<&'a SingleMessage> {
+    let locales: Vec<LanguageIdentifier> = candidates.iter().map(|m| m.locale.clone()).collect();
+    let chosen = crate::langid::resolve(requested, &locales)?;
+    candidates.iter().find(|m| &m.locale == chosen)
+}
+
+impl SingleMessage {
+    // The message's structured locale.
+    pub fn locale(&self) -> &LanguageIdentifier {
+        &self.locale
+    }
+
+    // Interpolate `args` into the pattern to produce the finished string.
+    //
+    // Each placeholder resolves its value by looking up `Placeholder.id` in
+    // `args`, then falling back to the placeholder's `default_text_val`, and
+    // finally erroring with `FormatError::MissingValue` if neither is present.
+    pub fn format(&self, args: &HashMap<String, ArgValue>) -> Result<String, FormatError> {
+        self.format_in(args, &MessageRegistry::new())
+    }
+
+    // As `format`, but with a registry so `MESSAGE_REF` parts can be resolved.
+    pub fn format_in(
+        &self,
+        args: &HashMap<String, ArgValue>,
+        registry: &MessageRegistry,
+    ) -> Result<String, FormatError> {
+        self.format_with(args, registry, &FormatterRegistry::new())
+    }
+
+    // As `format_in`, but also dispatching annotated placeholders through a
+    // `FormatterRegistry` of named functions (NUMBER, DATETIME, custom, ...).
+    pub fn format_with(
+        &self,
+        args: &HashMap<String, ArgValue>,
+        registry: &MessageRegistry,
+        functions: &FormatterRegistry,
+    ) -> Result<String, FormatError> {
+        let ctx = FormatContext { args, messages: registry, functions };
+        let mut in_progress = HashSet::new();
+        self.format_rec(&ctx, &mut in_progress)
+    }
+
+    // Core walk, tracking the set of message ids currently being formatted so
+    // reference cycles can be detected and rejected.
+    pub(crate) fn format_rec(
+        &self,
+        ctx: &FormatContext,
+        in_progress: &mut HashSet<String>,
+    ) -> Result<String, FormatError> {
+        let mut out = String::new();
+        for part in &self.pattern.parts {
+            match part {
+                PatternPart::TEXTPART(text_part) => out.push_str(&text_part.text),
+                PatternPart::PLACEHOLDER(ph) => {
+                    let val = if let Some(val) = ctx.args.get(&ph.id) {
+                        val.clone()
+                    } else if let Some(default) = &ph.default_text_val {
+                        ArgValue::String(default.clone())
+                    } else {
+                        return Err(FormatError::MissingValue(ph.id.clone()));
+                    };
+                    // an `OTHER("fn")` annotation dispatches through the named
+                    // function registry, falling back to a plain render.
+                    let locale = self.locale.to_string();
+                    let rendered = match &ph.ph_type {
+                        PlaceholderType::OTHER(name) => ctx
+                            .functions
+                            .format(name, &val, &FormatOptions::new(), &locale)
+                            .unwrap_or_else(|| val.format(&locale)),
+                        _ => val.format(&locale),
+                    };
+                    out.push_str(&rendered);
+                }
+                PatternPart::MESSAGE_REF(id) => {
+                    if in_progress.contains(id) {
+                        return Err(FormatError::CyclicReference(id.clone()));
+                    }
+                    let target = ctx
+                        .messages
+                        .get(id)
+                        .ok_or_else(|| FormatError::UnknownReference(id.clone()))?;
+                    in_progress.insert(id.clone());
+                    let rendered = target.format_rec(ctx, in_progress)?;
+                    in_progress.remove(id);
+                    out.push_str(&rendered);
+                }
+            }
+        }
+        Ok(out)
+    }
+}
 
-    locale: String,
-    pattern: MessagePattern,
-    ph_vals: PHValsMap, // type of value should prob be Any
+// The ambient state threaded through a single `format` call: the argument
+// values, the message registry for reference resolution, and the named-function
+// registry for annotated placeholders.
+pub struct FormatContext<'a> {
+    pub args: &'a HashMap<String, ArgValue>,
+    pub messages: &'a MessageRegistry,
+    pub functions: &'a FormatterRegistry,
 }
 
 impl fmt::Display for SingleMessage {
@@ -167,9 +464,75 @@ impl fmt::Display for SingleMessage {
     }
 }
 
+#[derive(Debug)]
 pub struct MessageGroup {
-    id: String,
-    messages: HashMap<PHValsMap, SingleMessage>,
+    pub(crate) id: String,
+    pub(crate) messages: HashMap<PHValsMap, SingleMessage>,
+    // the `.match` selector's own declared type (e.g. `:plural`), carried
+    // separately from the variant patterns so selection doesn't have to
+    // guess it back out of whichever variant happens to mention the var.
+    pub(crate) selector_type: PlaceholderType,
+}
+
+impl MessageGroup {
+    // Pick the `SingleMessage` that best matches the runtime arguments.
+    //
+    // Selection precedence mirrors MF2 / CLDR: an exact literal variant such as
+    // `=0` or `=5` wins first, then (for a `PLURAL` selector) the computed
+    // plural category for the variant messages' locale, and finally the `other`
+    // variant, which is required to always be present.
+    pub fn select(&self, args: &PHValsMap) -> &SingleMessage {
+        let var = self.selector_var();
+        let raw = args.map.get(&var).cloned().unwrap_or_default();
+
+        // 1. exact literal match: a variant keyed by the raw value (`=0`)...
+        if let Some(msg) = self.variant(&var, &format!("={}", raw)) {
+            return msg;
+        }
+        // ...or keyed by the value directly, which also covers GENDER strings.
+        if let Some(msg) = self.variant(&var, &raw) {
+            return msg;
+        }
+
+        // 2. computed plural category, when the selector is a plural.
+        if self.selector_type == PlaceholderType::PLURAL {
+            let locale = self
+                .messages
+                .values()
+                .next()
+                .map(|m| m.locale.to_string())
+                .unwrap_or_default();
+            let category = plural_category(&locale, &PluralOperands::from_number_str(&raw));
+            if let Some(msg) = self.variant(&var, category.as_key()) {
+                return msg;
+            }
+        }
+
+        // 3. guaranteed fallback.
+        self.variant(&var, "other")
+            .expect("a MessageGroup must always contain an `other` variant")
+    }
+
+    // The single selector variable shared by this group's variant keys.
+    fn selector_var(&self) -> String {
+        self.messages
+            .keys()
+            .next()
+            .and_then(|k| k.map.keys().next().cloned())
+            .unwrap_or_default()
+    }
+
+    // Look up the variant keyed by `{ var: key }`.
+    fn variant(&self, var: &str, key: &str) -> Option<&SingleMessage> {
+        let probe = PHValsMap {
+            map: {
+                let mut m = HashMap::new();
+                m.insert(var.to_string(), key.to_string());
+                m
+            },
+        };
+        self.messages.get(&probe)
+    }
 }
 
 impl fmt::Display for MessageGroup {
@@ -193,14 +556,62 @@ impl fmt::Display for MessageGroup {
     }
 }
 
+#[derive(Debug)]
 pub enum MessageType {
     SINGLE(SingleMessage),
     GROUP(MessageGroup)
 }
 
+impl MessageType {
+    // Format the message for the given arguments. For a `GROUP`, variant
+    // selection runs first (using the canonical display form of each argument
+    // as the selection value) and the chosen `SingleMessage` is then formatted.
+    pub fn format(&self, args: &HashMap<String, ArgValue>) -> Result<String, FormatError> {
+        self.format_in(args, &MessageRegistry::new())
+    }
+
+    // As `format`, but resolving `MESSAGE_REF` parts against `registry`.
+    pub fn format_in(
+        &self,
+        args: &HashMap<String, ArgValue>,
+        registry: &MessageRegistry,
+    ) -> Result<String, FormatError> {
+        self.format_with(args, registry, &FormatterRegistry::new())
+    }
+
+    // As `format_in`, but also dispatching annotated placeholders through
+    // `functions`.
+    pub fn format_with(
+        &self,
+        args: &HashMap<String, ArgValue>,
+        registry: &MessageRegistry,
+        functions: &FormatterRegistry,
+    ) -> Result<String, FormatError> {
+        let ctx = FormatContext { args, messages: registry, functions };
+        let mut in_progress = HashSet::new();
+        self.format_rec(&ctx, &mut in_progress)
+    }
+
+    pub(crate) fn format_rec(
+        &self,
+        ctx: &FormatContext,
+        in_progress: &mut HashSet<String>,
+    ) -> Result<String, FormatError> {
+        match self {
+            MessageType::SINGLE(msg) => msg.format_rec(ctx, in_progress),
+            MessageType::GROUP(grp) => {
+                let key = PHValsMap {
+                    map: ctx.args.iter().map(|(k, v)| (k.clone(), v.canonical())).collect(),
+                };
+                grp.select(&key).format_rec(ctx, in_progress)
+            }
+        }
+    }
+}
+
 pub struct TextUnit {
-    src: MessageType,
-    tgt: MessageType,
+    pub(crate) src: MessageType,
+    pub(crate) tgt: MessageType,
 }
 
 
@@ -272,17 +683,17 @@ mod tests {
 
         let msg1 = SingleMessage {
             id: String::from("msg1"),
-            locale: String::from("en"),
+            locale: LanguageIdentifier::parse("en"),
             pattern: MessagePattern{ 
                 parts: vec![
                     PatternPart::TEXTPART(TextPart{ text: String::from("No items selected.") }),
                 ],
             },
-            ph_vals: ph_vals1.clone(),
+            ph_vals: HashMap::new(),
         };
         let msg2 = SingleMessage {
             id: String::from("msg2"),
-            locale: String::from("en"),
+            locale: LanguageIdentifier::parse("en"),
             pattern: MessagePattern{ 
                 parts: vec![
                     PatternPart::PLACEHOLDER(Placeholder{
@@ -293,11 +704,11 @@ mod tests {
                     PatternPart::TEXTPART(TextPart{ text: String::from(" item selected.") }),
                 ],
             },
-            ph_vals: ph_vals2.clone(),
+            ph_vals: HashMap::new(),
         };
         let msg3 = SingleMessage {
             id: String::from("msg3"),
-            locale: String::from("en"),
+            locale: LanguageIdentifier::parse("en"),
             pattern: MessagePattern{ 
                 parts: vec![
                     PatternPart::PLACEHOLDER(Placeholder{
@@ -308,7 +719,7 @@ mod tests {
                     PatternPart::TEXTPART(TextPart{ text: String::from(" items selected.") }),
                 ],
             },
-            ph_vals: ph_vals3.clone(),
+            ph_vals: HashMap::new(),
         };
 
         let msg_grp_key_1 = ph_vals1.clone();
@@ -327,9 +738,190 @@ mod tests {
         let msg_grp = MessageGroup {
             id: String::from("msg_grp"),
             messages,
+            selector_type: PlaceholderType::PLURAL,
         };
 
         println!("msg_grp =");
         println!("{}", msg_grp);
     }
+
+    // helper: a plural `MessageGroup` with `=0`, `one`, `other` variants.
+    fn plural_group() -> MessageGroup {
+        let variant = |key: &str, text: &str| {
+            let ph_vals = PHValsMap {
+                map: {
+                    let mut m = HashMap::new();
+                    m.insert(String::from("COUNT"), String::from(key));
+                    m
+                },
+            };
+            (
+                ph_vals.clone(),
+                SingleMessage {
+                    id: format!("msg.{}", key),
+                    locale: LanguageIdentifier::parse("en"),
+                    pattern: MessagePattern {
+                        parts: vec![
+                            PatternPart::PLACEHOLDER(Placeholder {
+                                id: String::from("COUNT"),
+                                ph_type: PlaceholderType::PLURAL,
+                                default_text_val: None,
+                            }),
+                            PatternPart::TEXTPART(TextPart { text: String::from(text) }),
+                        ],
+                    },
+                    ph_vals: HashMap::new(),
+                },
+            )
+        };
+
+        let mut messages = HashMap::new();
+        for (k, m) in [variant("=0", ""), variant("one", " item"), variant("other", " items")] {
+            messages.insert(k, m);
+        }
+        MessageGroup { id: String::from("items"), messages, selector_type: PlaceholderType::PLURAL }
+    }
+
+    #[test]
+    fn test_select_plural() {
+        let grp = plural_group();
+        let arg = |v: &str| PHValsMap {
+            map: {
+                let mut m = HashMap::new();
+                m.insert(String::from("COUNT"), String::from(v));
+                m
+            },
+        };
+
+        // exact literal `=0` beats the computed category.
+        assert_eq!(grp.select(&arg("0")).id, "msg.=0");
+        // English `1` -> `one`.
+        assert_eq!(grp.select(&arg("1")).id, "msg.one");
+        // `5` -> `other` fallback.
+        assert_eq!(grp.select(&arg("5")).id, "msg.other");
+    }
+
+    #[test]
+    fn test_argvalue_number_formatting() {
+        let n = ArgValue::number(1234.0);
+        assert_eq!(n.format("en"), "1,234");
+        assert_eq!(n.format("de"), "1.234");
+        // grouping off still renders plainly.
+        let plain = ArgValue::Number(1234.0, NumberFormatOptions {
+            use_grouping: false,
+            ..NumberFormatOptions::default()
+        });
+        assert_eq!(plain.format("en"), "1234");
+        // equality/hashing ignore formatting options.
+        assert_eq!(n, plain);
+    }
+
+    #[test]
+    fn test_format_single_and_group() {
+        let grp = MessageType::GROUP(plural_group());
+        let mut args = HashMap::new();
+        args.insert(String::from("COUNT"), ArgValue::number(1.0));
+        // `one` variant selected, `COUNT` interpolated.
+        assert_eq!(grp.format(&args).unwrap(), "1 item");
+
+        args.insert(String::from("COUNT"), ArgValue::number(0.0));
+        // exact `=0` variant wins over the computed category.
+        assert_eq!(grp.format(&args).unwrap(), "0");
+    }
+
+    #[test]
+    fn test_format_missing_value_errors() {
+        let msg = SingleMessage {
+            id: String::from("m"),
+            locale: LanguageIdentifier::parse("en"),
+            pattern: MessagePattern {
+                parts: vec![PatternPart::PLACEHOLDER(Placeholder {
+                    id: String::from("NAME"),
+                    ph_type: PlaceholderType::UNKNOWN,
+                    default_text_val: None,
+                })],
+            },
+            ph_vals: HashMap::new(),
+        };
+        assert_eq!(
+            msg.format(&HashMap::new()).unwrap_err(),
+            FormatError::MissingValue(String::from("NAME"))
+        );
+    }
+
+    // helper: a single message with a fixed pattern of text and refs.
+    fn single(id: &str, parts: Vec<PatternPart>) -> MessageType {
+        MessageType::SINGLE(SingleMessage {
+            id: String::from(id),
+            locale: LanguageIdentifier::parse("en"),
+            pattern: MessagePattern { parts },
+            ph_vals: HashMap::new(),
+        })
+    }
+
+    #[test]
+    fn test_message_reference_resolves() {
+        let mut registry = MessageRegistry::new();
+        registry.insert(
+            String::from("brand"),
+            single("brand", vec![PatternPart::TEXTPART(TextPart { text: String::from("Acme") })]),
+        );
+        let greeting = single(
+            "greeting",
+            vec![
+                PatternPart::TEXTPART(TextPart { text: String::from("Welcome to ") }),
+                PatternPart::MESSAGE_REF(String::from("brand")),
+                PatternPart::TEXTPART(TextPart { text: String::from("!") }),
+            ],
+        );
+        assert_eq!(greeting.format_in(&HashMap::new(), &registry).unwrap(), "Welcome to Acme!");
+    }
+
+    #[test]
+    fn test_message_reference_cycle_detected() {
+        let mut registry = MessageRegistry::new();
+        registry.insert("a".into(), single("a", vec![PatternPart::MESSAGE_REF(String::from("b"))]));
+        registry.insert("b".into(), single("b", vec![PatternPart::MESSAGE_REF(String::from("a"))]));
+        let start = single("start", vec![PatternPart::MESSAGE_REF(String::from("a"))]);
+        assert!(matches!(
+            start.format_in(&HashMap::new(), &registry),
+            Err(FormatError::CyclicReference(_))
+        ));
+    }
+
+    #[test]
+    fn test_format_dispatches_named_function() {
+        let msg = SingleMessage {
+            id: String::from("m"),
+            locale: LanguageIdentifier::parse("en"),
+            pattern: MessagePattern {
+                parts: vec![PatternPart::PLACEHOLDER(Placeholder {
+                    id: String::from("PRICE"),
+                    ph_type: PlaceholderType::OTHER(String::from("number")),
+                    default_text_val: None,
+                })],
+            },
+            ph_vals: HashMap::new(),
+        };
+        let mut args = HashMap::new();
+        args.insert(String::from("PRICE"), ArgValue::number(1234.0));
+        let out = msg
+            .format_with(&args, &MessageRegistry::new(), &crate::function::FormatterRegistry::new())
+            .unwrap();
+        assert_eq!(out, "1,234");
+    }
+
+    #[test]
+    fn test_resolve_message_regional_fallback() {
+        let make = |loc: &str| SingleMessage {
+            id: format!("m.{}", loc),
+            locale: LanguageIdentifier::parse(loc),
+            pattern: MessagePattern { parts: vec![] },
+            ph_vals: HashMap::new(),
+        };
+        let candidates = [make("en"), make("fr")];
+        let chosen = resolve_message(&LanguageIdentifier::parse("en-GB"), &candidates).unwrap();
+        assert_eq!(chosen.locale().to_string(), "en");
+        assert!(resolve_message(&LanguageIdentifier::parse("de"), &candidates).is_none());
+    }
 }
\ No newline at end of file
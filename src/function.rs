@@ -0,0 +1,227 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::data::ArgValue;
+
+// Named-function options, passed to `FormatterRegistry::format`. Kept as
+// plain string pairs (`"minimumFractionDigits" => "2"`) so the open-ended
+// `PlaceholderType::OTHER` surface stays free-form. Placeholders carry no
+// options of their own, so `SingleMessage::format_with` passes an empty set.
+pub type FormatOptions = HashMap<String, String>;
+
+// A locale-bound formatter instance, analogous to a constructed `Intl.*`
+// object. Building one can be costly, so instances are memoized by the
+// registry (see `FormatterRegistry`).
+pub trait IntlFormatter {
+    fn format(&self, value: &ArgValue, options: &FormatOptions) -> String;
+}
+
+// Builds a formatter instance bound to a single locale.
+pub type FormatterConstructor = Box<dyn Fn(&str) -> Box<dyn IntlFormatter>>;
+
+// A registry of named formatters invokable from `PlaceholderType::OTHER(name)`,
+// mirroring the function table on a Fluent bundle. Constructed locale-bound
+// instances are memoized by `(locale, function)` so the locale-dependent state
+// is built at most once per pair, like Fluent's `IntlLangMemoizer`.
+pub struct FormatterRegistry {
+    constructors: HashMap<String, FormatterConstructor>,
+    memo: RefCell<HashMap<(String, String), Rc<dyn IntlFormatter>>>,
+}
+
+impl FormatterRegistry {
+    // A registry pre-populated with the `number` and `datetime` built-ins.
+    pub fn new() -> FormatterRegistry {
+        let mut registry = FormatterRegistry {
+            constructors: HashMap::new(),
+            memo: RefCell::new(HashMap::new()),
+        };
+        registry.register("number", |locale| {
+            Box::new(NumberFormatter { locale: locale.to_string() })
+        });
+        registry.register("datetime", |locale| {
+            Box::new(DateTimeFormatter { locale: locale.to_string() })
+        });
+        registry
+    }
+
+    // Register (or replace) a named formatter with its locale-bound constructor.
+    pub fn register<F>(&mut self, name: &str, constructor: F)
+    where
+        F: Fn(&str) -> Box<dyn IntlFormatter> + 'static,
+    {
+        self.constructors.insert(name.to_string(), Box::new(constructor));
+        // drop any memoized instances so a re-registration takes effect.
+        self.memo.borrow_mut().retain(|(_, fname), _| fname.as_str() != name);
+    }
+
+    // Format `value` through the named function for `locale`, returning `None`
+    // if no such function is registered. The constructed instance is memoized.
+    pub fn format(
+        &self,
+        name: &str,
+        value: &ArgValue,
+        options: &FormatOptions,
+        locale: &str,
+    ) -> Option<String> {
+        if !self.constructors.contains_key(name) {
+            return None;
+        }
+        let key = (locale.to_string(), name.to_string());
+        let instance = {
+            let mut memo = self.memo.borrow_mut();
+            if let Some(existing) = memo.get(&key) {
+                Rc::clone(existing)
+            } else {
+                let ctor = self.constructors.get(name).unwrap();
+                let built: Rc<dyn IntlFormatter> = Rc::from(ctor(locale));
+                memo.insert(key, Rc::clone(&built));
+                built
+            }
+        };
+        Some(instance.format(value, options))
+    }
+}
+
+impl Default for FormatterRegistry {
+    fn default() -> Self {
+        FormatterRegistry::new()
+    }
+}
+
+// Built-in numeric formatter. Annotation options override the number's own
+// `NumberFormatOptions`.
+struct NumberFormatter {
+    locale: String,
+}
+
+impl IntlFormatter for NumberFormatter {
+    fn format(&self, value: &ArgValue, options: &FormatOptions) -> String {
+        match value {
+            ArgValue::Number(n, base) => {
+                let mut opts = base.clone();
+                if let Some(v) = parse_opt(options, "minimumFractionDigits") {
+                    opts.minimum_fraction_digits = v;
+                }
+                if let Some(v) = parse_opt(options, "maximumFractionDigits") {
+                    opts.maximum_fraction_digits = v;
+                }
+                if let Some(v) = options.get("useGrouping").and_then(|s| s.parse().ok()) {
+                    opts.use_grouping = v;
+                }
+                ArgValue::Number(*n, opts).format(&self.locale)
+            }
+            other => other.format(&self.locale),
+        }
+    }
+}
+
+// Built-in date/time formatter. Takes its argument as a Unix epoch (seconds,
+// UTC) and renders a fixed `YYYY-MM-DD HH:MM:SS` form; there's no real
+// locale-specific calendar backend yet, so `locale` is accepted but unused.
+// Anything that isn't a number (already a date string, say) passes through
+// unchanged.
+struct DateTimeFormatter {
+    locale: String,
+}
+
+impl IntlFormatter for DateTimeFormatter {
+    fn format(&self, value: &ArgValue, _options: &FormatOptions) -> String {
+        match value {
+            ArgValue::Number(epoch_secs, _) => format_epoch_utc(*epoch_secs),
+            other => other.format(&self.locale),
+        }
+    }
+}
+
+// Render a Unix epoch timestamp (seconds, UTC) as `YYYY-MM-DD HH:MM:SS`.
+fn format_epoch_utc(epoch_secs: f64) -> String {
+    let total_secs = epoch_secs.floor() as i64;
+    let days = total_secs.div_euclid(86400);
+    let secs_of_day = total_secs.rem_euclid(86400);
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        (secs_of_day % 3600) / 60,
+        secs_of_day % 60,
+    )
+}
+
+// Days-since-Unix-epoch to proleptic-Gregorian (year, month, day), per Howard
+// Hinnant's `civil_from_days` (public domain; date algorithms paper).
+fn civil_from_days(days_since_epoch: i64) -> (i64, u32, u32) {
+    let z = days_since_epoch + 719468;
+    let era = if z >= 0 { z } else { z - 146096 } / 146097;
+    let doe = z - era * 146097; // [0, 146096]
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365; // [0, 399]
+    let y = yoe + era * 400;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100); // [0, 365]
+    let mp = (5 * doy + 2) / 153; // [0, 11]
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32; // [1, 31]
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32; // [1, 12]
+    let year = if month <= 2 { y + 1 } else { y };
+    (year, month, day)
+}
+
+fn parse_opt(options: &FormatOptions, key: &str) -> Option<usize> {
+    options.get(key).and_then(|s| s.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number_builtin_applies_options() {
+        let registry = FormatterRegistry::new();
+        let mut opts = FormatOptions::new();
+        opts.insert(String::from("minimumFractionDigits"), String::from("2"));
+        let out = registry
+            .format("number", &ArgValue::number(5.0), &opts, "en")
+            .unwrap();
+        assert_eq!(out, "5.00");
+    }
+
+    #[test]
+    fn test_unknown_function_returns_none() {
+        let registry = FormatterRegistry::new();
+        assert!(registry
+            .format("nope", &ArgValue::number(1.0), &FormatOptions::new(), "en")
+            .is_none());
+    }
+
+    #[test]
+    fn test_register_custom_function() {
+        let mut registry = FormatterRegistry::new();
+        registry.register("shout", |_locale| Box::new(Shout));
+        let out = registry
+            .format("shout", &ArgValue::String(String::from("hi")), &FormatOptions::new(), "en")
+            .unwrap();
+        assert_eq!(out, "HI");
+    }
+
+    #[test]
+    fn test_datetime_builtin_renders_epoch() {
+        let registry = FormatterRegistry::new();
+        let opts = FormatOptions::new();
+        assert_eq!(
+            registry.format("datetime", &ArgValue::number(0.0), &opts, "en").unwrap(),
+            "1970-01-01 00:00:00"
+        );
+        assert_eq!(
+            registry.format("datetime", &ArgValue::number(1_700_000_000.0), &opts, "en").unwrap(),
+            "2023-11-14 22:13:20"
+        );
+    }
+
+    struct Shout;
+    impl IntlFormatter for Shout {
+        fn format(&self, value: &ArgValue, _options: &FormatOptions) -> String {
+            value.canonical().to_uppercase()
+        }
+    }
+}
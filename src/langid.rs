@@ -0,0 +1,177 @@
+use std::fmt;
+use std::str::FromStr;
+
+// A parsed BCP-47 language identifier (language / script / region / variants),
+// in the spirit of `unic-langid`. Subtags are normalized on parse so matching
+// is case-insensitive: language lowercase, script title-case, region upper-case,
+// variants lowercase.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LanguageIdentifier {
+    language: String,
+    script: Option<String>,
+    region: Option<String>,
+    variants: Vec<String>,
+}
+
+impl LanguageIdentifier {
+    // Parse a BCP-47 string such as `en`, `en-GB`, or `zh-Hant-TW`. Both `-` and
+    // `_` are accepted as separators. An empty or missing language becomes the
+    // `und` ("undetermined") root.
+    pub fn parse(input: &str) -> LanguageIdentifier {
+        let mut parts = input.split(['-', '_']).filter(|s| !s.is_empty());
+
+        let language = match parts.next() {
+            Some(lang) => lang.to_ascii_lowercase(),
+            None => String::from("und"),
+        };
+
+        let mut script = None;
+        let mut region = None;
+        let mut variants = Vec::new();
+        for subtag in parts {
+            if script.is_none() && region.is_none() && is_script(subtag) {
+                script = Some(title_case(subtag));
+            } else if region.is_none() && is_region(subtag) {
+                region = Some(subtag.to_ascii_uppercase());
+            } else {
+                variants.push(subtag.to_ascii_lowercase());
+            }
+        }
+
+        LanguageIdentifier { language, script, region, variants }
+    }
+
+    // The language subtag, e.g. `en` — what the plural selector needs.
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    // The region subtag, if any, e.g. `GB`.
+    pub fn region(&self) -> Option<&str> {
+        self.region.as_deref()
+    }
+
+    // The script subtag, if any, e.g. `Hant`.
+    pub fn script(&self) -> Option<&str> {
+        self.script.as_deref()
+    }
+
+    // The fallback chain from most to least specific, dropping variants, then
+    // the region, then the script, ending at the bare language. For example
+    // `en-Latn-GB` yields `en-Latn-GB`, `en-Latn`, `en`.
+    pub fn fallback_chain(&self) -> Vec<LanguageIdentifier> {
+        let mut chain = vec![self.clone()];
+
+        if !self.variants.is_empty() {
+            let mut id = self.clone();
+            id.variants.clear();
+            chain.push(id);
+        }
+        if self.region.is_some() {
+            let mut id = chain.last().unwrap().clone();
+            id.region = None;
+            chain.push(id);
+        }
+        if self.script.is_some() {
+            let mut id = chain.last().unwrap().clone();
+            id.script = None;
+            chain.push(id);
+        }
+
+        chain
+    }
+}
+
+impl FromStr for LanguageIdentifier {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(LanguageIdentifier::parse(s))
+    }
+}
+
+impl From<&str> for LanguageIdentifier {
+    fn from(s: &str) -> Self {
+        LanguageIdentifier::parse(s)
+    }
+}
+
+impl fmt::Display for LanguageIdentifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.language)?;
+        if let Some(script) = &self.script {
+            write!(f, "-{}", script)?;
+        }
+        if let Some(region) = &self.region {
+            write!(f, "-{}", region)?;
+        }
+        for variant in &self.variants {
+            write!(f, "-{}", variant)?;
+        }
+        Ok(())
+    }
+}
+
+// Walk `requested`'s fallback chain and return the closest identifier present
+// in `available`. Falls back to any entry sharing the language, then `None`.
+pub fn resolve<'a>(
+    requested: &LanguageIdentifier,
+    available: &'a [LanguageIdentifier],
+) -> Option<&'a LanguageIdentifier> {
+    for candidate in requested.fallback_chain() {
+        if let Some(found) = available.iter().find(|a| **a == candidate) {
+            return Some(found);
+        }
+    }
+    available.iter().find(|a| a.language == requested.language)
+}
+
+fn is_script(subtag: &str) -> bool {
+    subtag.len() == 4 && subtag.chars().all(|c| c.is_ascii_alphabetic())
+}
+
+fn is_region(subtag: &str) -> bool {
+    (subtag.len() == 2 && subtag.chars().all(|c| c.is_ascii_alphabetic()))
+        || (subtag.len() == 3 && subtag.chars().all(|c| c.is_ascii_digit()))
+}
+
+fn title_case(subtag: &str) -> String {
+    let mut chars = subtag.chars();
+    match chars.next() {
+        Some(first) => {
+            first.to_ascii_uppercase().to_string() + &chars.as_str().to_ascii_lowercase()
+        }
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display_roundtrip() {
+        let id = LanguageIdentifier::parse("zh_hant_tw");
+        assert_eq!(id.language(), "zh");
+        assert_eq!(id.script(), Some("Hant"));
+        assert_eq!(id.region(), Some("TW"));
+        assert_eq!(id.to_string(), "zh-Hant-TW");
+    }
+
+    #[test]
+    fn test_fallback_chain() {
+        let chain: Vec<String> = LanguageIdentifier::parse("en-Latn-GB")
+            .fallback_chain()
+            .iter()
+            .map(|id| id.to_string())
+            .collect();
+        assert_eq!(chain, vec!["en-Latn-GB", "en-Latn", "en"]);
+    }
+
+    #[test]
+    fn test_resolve_regional_fallback() {
+        let available = [LanguageIdentifier::parse("en"), LanguageIdentifier::parse("fr")];
+        let resolved = resolve(&LanguageIdentifier::parse("en-GB"), &available).unwrap();
+        assert_eq!(resolved.to_string(), "en");
+    }
+}
@@ -0,0 +1,5 @@
+pub mod data;
+pub mod function;
+pub mod langid;
+pub mod parser;
+pub mod plural;
@@ -0,0 +1,140 @@
+// Plural selection support, modeled on the CLDR plural-rules operands.
+//
+// Only a handful of locales carry bespoke rules here (English, French and
+// Polish as a representative three-way language); everything else falls back
+// to the `Other` category, which the selector always treats as present.
+
+// The six CLDR plural operands derived from a formatted number:
+//   n  absolute value of the number
+//   i  integer digits
+//   v  count of visible fraction digits, with trailing zeros
+//   w  count of visible fraction digits, without trailing zeros
+//   f  visible fraction digits as an integer, with trailing zeros
+//   t  visible fraction digits as an integer, without trailing zeros
+#[derive(Debug, Clone, PartialEq)]
+pub struct PluralOperands {
+    pub n: f64,
+    pub i: u64,
+    pub v: usize,
+    pub w: usize,
+    pub f: u64,
+    pub t: u64,
+}
+
+impl PluralOperands {
+    // Derive the operands from the canonical display form of a number, e.g.
+    // `"1.230"` yields i=1, v=3, w=2, f=230, t=23.
+    //
+    // Named `from_number_str` rather than `from_str` so this stays an
+    // inherent method instead of colliding with (and tripping clippy's
+    // should_implement_trait lint for) `std::str::FromStr`.
+    pub fn from_number_str(s: &str) -> PluralOperands {
+        let s = s.trim();
+        let unsigned = s.trim_start_matches(['-', '+']);
+        let (int_str, frac_str) = match unsigned.split_once('.') {
+            Some((a, b)) => (a, b),
+            None => (unsigned, ""),
+        };
+
+        let n: f64 = unsigned.parse().unwrap_or(0.0);
+        let i: u64 = int_str.parse().unwrap_or(0);
+        let v = frac_str.len();
+        let f: u64 = if frac_str.is_empty() { 0 } else { frac_str.parse().unwrap_or(0) };
+        let frac_nz = frac_str.trim_end_matches('0');
+        let w = frac_nz.len();
+        let t: u64 = if frac_nz.is_empty() { 0 } else { frac_nz.parse().unwrap_or(0) };
+
+        PluralOperands { n, i, v, w, f, t }
+    }
+}
+
+// The CLDR plural categories, in their canonical order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PluralCategory {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other,
+}
+
+impl PluralCategory {
+    // The lowercase keyword a variant is keyed by (`one`, `other`, ...).
+    pub fn as_key(&self) -> &'static str {
+        match self {
+            PluralCategory::Zero => "zero",
+            PluralCategory::One => "one",
+            PluralCategory::Two => "two",
+            PluralCategory::Few => "few",
+            PluralCategory::Many => "many",
+            PluralCategory::Other => "other",
+        }
+    }
+}
+
+// Evaluate the plural rule set for `locale`'s language and return the matching
+// category. The language subtag is matched case-insensitively.
+pub fn plural_category(locale: &str, op: &PluralOperands) -> PluralCategory {
+    let lang = locale.split(['-', '_']).next().unwrap_or("").to_ascii_lowercase();
+    match lang.as_str() {
+        "en" => {
+            if op.i == 1 && op.v == 0 {
+                PluralCategory::One
+            } else {
+                PluralCategory::Other
+            }
+        }
+        "fr" => {
+            if op.i == 0 || op.i == 1 {
+                PluralCategory::One
+            } else {
+                PluralCategory::Other
+            }
+        }
+        "pl" => {
+            if op.i == 1 && op.v == 0 {
+                PluralCategory::One
+            } else if op.v == 0
+                && (2..=4).contains(&(op.i % 10))
+                && !(12..=14).contains(&(op.i % 100))
+            {
+                PluralCategory::Few
+            } else if op.v == 0 {
+                PluralCategory::Many
+            } else {
+                PluralCategory::Other
+            }
+        }
+        _ => PluralCategory::Other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operands_from_number_str() {
+        let op = PluralOperands::from_number_str("1.230");
+        assert_eq!(op.i, 1);
+        assert_eq!(op.v, 3);
+        assert_eq!(op.w, 2);
+        assert_eq!(op.f, 230);
+        assert_eq!(op.t, 23);
+    }
+
+    #[test]
+    fn test_english_rules() {
+        assert_eq!(plural_category("en", &PluralOperands::from_number_str("1")), PluralCategory::One);
+        assert_eq!(plural_category("en-US", &PluralOperands::from_number_str("2")), PluralCategory::Other);
+        assert_eq!(plural_category("en", &PluralOperands::from_number_str("1.0")), PluralCategory::Other);
+    }
+
+    #[test]
+    fn test_polish_rules() {
+        assert_eq!(plural_category("pl", &PluralOperands::from_number_str("1")), PluralCategory::One);
+        assert_eq!(plural_category("pl", &PluralOperands::from_number_str("3")), PluralCategory::Few);
+        assert_eq!(plural_category("pl", &PluralOperands::from_number_str("5")), PluralCategory::Many);
+    }
+}